@@ -0,0 +1,112 @@
+// Turns raw CHIP-8 ROM bytes into human-readable assembly, mirroring the same
+// nibble decomposition `Emu::execute` uses so the two stay in sync.
+
+use crate::START_ADDR;
+
+// Walks `rom` two bytes at a time, reconstructing each opcode as `(hi << 8) | lo`,
+// and returns one (address, mnemonic) pair per opcode in program order. Addresses
+// start at START_ADDR, matching where a ROM is loaded into RAM.
+pub fn disassemble(rom: &[u8]) -> Vec<(u16, String)> {
+    let mut out = Vec::new();
+    let mut addr = START_ADDR;
+
+    let mut chunks = rom.chunks_exact(2);
+    for chunk in &mut chunks {
+        let hi = chunk[0] as u16;
+        let lo = chunk[1] as u16;
+        let op = (hi << 8) | lo;
+
+        out.push((addr, mnemonic(op)));
+        addr += 2;
+    }
+
+    out
+}
+
+// Decodes a single opcode into its mnemonic, e.g. `LD V3, 0x2A`, `JP 0x2E0`.
+fn mnemonic(op: u16) -> String {
+    let digit1 = (op & 0xF000) >> 12;
+    let digit2 = (op & 0x0F00) >> 8;
+    let digit3 = (op & 0x00F0) >> 4;
+    let digit4 = op & 0x000F;
+
+    let nnn = op & 0xFFF;
+    let nn = (op & 0xFF) as u8;
+    let x = digit2;
+    let y = digit3;
+    let n = digit4;
+
+    match (digit1, digit2, digit3, digit4) {
+        (0, 0, 0, 0) => "NOP".to_string(),
+        (0, 0, 0xC, _) => format!("SCD {}", n),
+        (0, 0, 0xE, 0) => "CLS".to_string(),
+        (0, 0, 0xE, 0xE) => "RET".to_string(),
+        (0, 0, 0xF, 0xB) => "SCR".to_string(),
+        (0, 0, 0xF, 0xC) => "SCL".to_string(),
+        (0, 0, 0xF, 0xE) => "LOW".to_string(),
+        (0, 0, 0xF, 0xF) => "HIGH".to_string(),
+        (1, _, _, _) => format!("JP {:#X}", nnn),
+        (2, _, _, _) => format!("CALL {:#X}", nnn),
+        (3, _, _, _) => format!("SE V{:X}, {:#X}", x, nn),
+        (4, _, _, _) => format!("SNE V{:X}, {:#X}", x, nn),
+        (5, _, _, 0) => format!("SE V{:X}, V{:X}", x, y),
+        (6, _, _, _) => format!("LD V{:X}, {:#X}", x, nn),
+        (7, _, _, _) => format!("ADD V{:X}, {:#X}", x, nn),
+        (8, _, _, 0) => format!("LD V{:X}, V{:X}", x, y),
+        (8, _, _, 1) => format!("OR V{:X}, V{:X}", x, y),
+        (8, _, _, 2) => format!("AND V{:X}, V{:X}", x, y),
+        (8, _, _, 3) => format!("XOR V{:X}, V{:X}", x, y),
+        (8, _, _, 4) => format!("ADD V{:X}, V{:X}", x, y),
+        (8, _, _, 5) => format!("SUB V{:X}, V{:X}", x, y),
+        (8, _, _, 6) => format!("SHR V{:X}", x),
+        (8, _, _, 7) => format!("SUBN V{:X}, V{:X}", x, y),
+        (8, _, _, 0xE) => format!("SHL V{:X}", x),
+        (9, _, _, 0) => format!("SNE V{:X}, V{:X}", x, y),
+        (0xA, _, _, _) => format!("LD I, {:#X}", nnn),
+        (0xB, _, _, _) => format!("JP V0, {:#X}", nnn),
+        (0xC, _, _, _) => format!("RND V{:X}, {:#X}", x, nn),
+        (0xD, _, _, _) => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        (0xE, _, 9, 0xE) => format!("SKP V{:X}", x),
+        (0xE, _, 0xA, 1) => format!("SKNP V{:X}", x),
+        (0xF, _, 0, 7) => format!("LD V{:X}, DT", x),
+        (0xF, _, 0, 0xA) => format!("LD V{:X}, K", x),
+        (0xF, _, 1, 5) => format!("LD DT, V{:X}", x),
+        (0xF, _, 1, 8) => format!("LD ST, V{:X}", x),
+        (0xF, _, 1, 0xE) => format!("ADD I, V{:X}", x),
+        (0xF, _, 2, 9) => format!("LD F, V{:X}", x),
+        (0xF, _, 3, 0) => format!("LD HF, V{:X}", x),
+        (0xF, _, 3, 3) => format!("LD B, V{:X}", x),
+        (0xF, _, 5, 5) => format!("LD [I], V{:X}", x),
+        (0xF, _, 6, 5) => format!("LD V{:X}, [I]", x),
+        (0xF, _, 7, 5) => format!("LD R, V{:X}", x),
+        (0xF, _, 8, 5) => format!("LD V{:X}, R", x),
+        (_, _, _, _) => format!("DATA {:#06X}", op),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_schip_opcodes() {
+        let rom = [
+            0x00, 0xFF, // HIGH
+            0x00, 0xFE, // LOW
+            0x00, 0xC3, // SCD 3
+            0x00, 0xFB, // SCR
+            0x00, 0xFC, // SCL
+            0xF1, 0x30, // LD HF, V1
+            0xF2, 0x75, // LD R, V2
+            0xF2, 0x85, // LD V2, R
+        ];
+
+        let out = disassemble(&rom);
+        let mnemonics: Vec<&str> = out.iter().map(|(_, m)| m.as_str()).collect();
+
+        assert_eq!(
+            mnemonics,
+            vec!["HIGH", "LOW", "SCD 3", "SCR", "SCL", "LD HF, V1", "LD R, V2", "LD V2, R"]
+        );
+    }
+}
@@ -1,15 +1,26 @@
 // This file is the core of our emulator
 
+use rand::random;
+
+mod disassembler;
+pub use disassembler::disassemble;
+
 // chip8 has a 64x32 bit monochrome display, public so the frontend has access
 pub const SCREEN_WIDTH: usize = 64;
 pub const SCREEN_HEIGHT: usize = 32;
 
+// Super-CHIP adds an optional 128x64 hi-res display mode, toggled at runtime via
+// the 00FF/00FE opcodes. `screen` is always sized to this larger extent so
+// switching modes never needs to reallocate.
+pub const HIRES_SCREEN_WIDTH: usize = 128;
+pub const HIRES_SCREEN_HEIGHT: usize = 64;
+
 const RAM_SIZE: usize = 4096; // Typical RAM size (KB) for chip8 emulator
 const NUM_REGS: usize = 16; // chip8 has 16 8-bit registers which are much faster to access when compared RAM called V registers
 const STACK_SIZE: usize = 16; // Stack can hold 16 numbers. It is used to return to starting point
                               // after a subroutine ends
 const NUM_KEYS: usize = 16;
-const START_ADDR: u16 = 0x200; // PC must start at 512th bit according to chip8 specifications.
+pub(crate) const START_ADDR: u16 = 0x200; // PC must start at 512th bit according to chip8 specifications.
                                // 0x200 represents 512 in hex.
 const FONTSET_SIZE: usize = 80;
 
@@ -33,10 +44,96 @@ const FONTSET: [u8; FONTSET_SIZE] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80 // F
 ];
 
+const LARGE_FONTSET_SIZE: usize = 160;
+
+// Super-CHIP's large font, used by FX30 to render bigger hex digits. Each glyph
+// is 10 bytes tall and 8 bits wide, stored right after the regular FONTSET in ram.
+const LARGE_FONTSET: [u8; LARGE_FONTSET_SIZE] = [
+    0x3C, 0x7E, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0x7E, 0x3C, // 0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+    0x7E, 0xC3, 0x03, 0x0E, 0x18, 0x30, 0x60, 0xC0, 0xC3, 0xFF, // 2
+    0x7E, 0xC3, 0x03, 0x03, 0x3E, 0x03, 0x03, 0x03, 0xC3, 0x7E, // 3
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0x06, 0x06, 0x06, // 4
+    0xFF, 0xC0, 0xC0, 0xC0, 0xFC, 0x06, 0x03, 0x03, 0xC3, 0x7E, // 5
+    0x7E, 0xC3, 0xC0, 0xC0, 0xFC, 0xC6, 0xC3, 0xC3, 0xC3, 0x7E, // 6
+    0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, 0x60, // 7
+    0x7E, 0xC3, 0xC3, 0xC3, 0x7E, 0xC3, 0xC3, 0xC3, 0xC3, 0x7E, // 8
+    0x7E, 0xC3, 0xC3, 0xC3, 0x7F, 0x03, 0x03, 0x03, 0xC3, 0x7E, // 9
+    0x18, 0x3C, 0x66, 0xC3, 0xC3, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xC6, 0xC3, 0xC3, 0xFC, 0xC3, 0xC3, 0xC3, 0xC6, 0xFC, // B
+    0x7E, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0x7E, // C
+    0xFC, 0xC6, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC6, 0xFC, // D
+    0xFF, 0xC0, 0xC0, 0xC0, 0xFC, 0xC0, 0xC0, 0xC0, 0xC0, 0xFF, // E
+    0xFF, 0xC0, 0xC0, 0xC0, 0xFC, 0xC0, 0xC0, 0xC0, 0xC0, 0xC0, // F
+];
+
+const NUM_FLAGS: usize = 8; // SCHIP's RPL "flags" registers, persisted by FX75/FX85
+
+// Several opcodes are ambiguous across CHIP-8 interpreters: the original COSMAC
+// VIP behaves one way, and most modern interpreters (and the CHIP-8 test suite)
+// behave another. `Quirks` lets a frontend pick which set of behaviors to use
+// instead of editing `execute` directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    // 8XY6/8XYE: if true, shift VX in place; if false, copy VY into VX first,
+    // then shift (the original COSMAC VIP behavior)
+    pub shift_in_place: bool,
+    // FX55/FX65: if true, i_reg is left unchanged; if false, it is incremented
+    // by X + 1 after the load/store (the original COSMAC VIP behavior)
+    pub load_store_leaves_i: bool,
+    // BNNN: if true, jump to NNN + VX; if false, jump to NNN + V0 (the original
+    // COSMAC VIP behavior)
+    pub jump_uses_vx: bool,
+    // DXYN: if true, sprites are clipped at the screen edge; if false, they
+    // wrap around (the original COSMAC VIP behavior is to clip)
+    pub clip_sprites: bool,
+}
+
+impl Quirks {
+    // COSMAC VIP behavior: copy-then-shift, i_reg advances on FX55/FX65, BNNN is
+    // V0-relative, and sprites clip at the screen edge.
+    pub fn cosmac_vip() -> Self {
+        Self {
+            shift_in_place: false,
+            load_store_leaves_i: false,
+            jump_uses_vx: false,
+            clip_sprites: true,
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self::cosmac_vip()
+    }
+}
+
+const SNAPSHOT_VERSION: u8 = 2;
+
+// Total byte length of a blob produced by `Emu::snapshot`: a one-byte format
+// version followed by pc, ram, screen, hires, v_reg, i_reg, sp, stack, keys,
+// dt, st, flags. `quirks` is deliberately excluded: it's caller-supplied
+// configuration picked via `with_quirks`, not machine state produced by
+// execution, so `restore` leaves the receiving `Emu`'s quirks untouched.
+const SNAPSHOT_LEN: usize = 1
+    + 2
+    + RAM_SIZE
+    + HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT
+    + 1
+    + NUM_REGS
+    + 2
+    + 2
+    + STACK_SIZE * 2
+    + NUM_KEYS
+    + 1
+    + 1
+    + NUM_FLAGS;
+
 pub struct Emu {
     pc: u16,  // This is the program counter, it is a special register that keeps an index of the current instruction
     ram: [u8; RAM_SIZE], // Array of 8 bit digits representin RAM
-    screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+    screen: [bool; HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT],
+    hires: bool, // Super-CHIP 128x64 mode, toggled by the 00FF/00FE opcodes
     v_reg: [u8; NUM_REGS],
     i_reg: u16, // I register: used to index into RAM
     sp: u16, // Stack Pointer: Keeps index of the top of the stack
@@ -45,16 +142,25 @@ pub struct Emu {
     dt: u8, // Delay Timer: Counts down every clock cycle, and performs an action when it hits 0
     st: u8, // Sound Timer: Counts down every clock cycle, and emits audio when it hits 0. This is
             // the only way to emit audio on the Chip-8
+    flags: [u8; NUM_FLAGS], // SCHIP RPL flags, saved/restored by FX75/FX85
+    quirks: Quirks,
 }
 
 // This is the constructor for the Emu struct, we will initialize everything to 0 by default
 
 impl Emu {
     pub fn new() -> Self {
+        Self::with_quirks(Quirks::default())
+    }
+
+    // Builds an Emu configured with a specific set of ambiguous-opcode behaviors,
+    // so ROMs written for a particular interpreter run correctly
+    pub fn with_quirks(quirks: Quirks) -> Self {
         let mut new_emu = Self {
             pc: START_ADDR,
             ram: [0; RAM_SIZE],
-            screen: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
+            screen: [false; HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT],
+            hires: false,
             v_reg: [0; NUM_REGS],
             i_reg: 0,
             sp: 0,
@@ -62,10 +168,13 @@ impl Emu {
             keys: [false; NUM_KEYS],
             dt: 0,
             st: 0,
+            flags: [0; NUM_FLAGS],
+            quirks,
         };
 
         new_emu.ram[..FONTSET_SIZE].copy_from_slice(&FONTSET);
-        
+        new_emu.ram[FONTSET_SIZE..FONTSET_SIZE + LARGE_FONTSET_SIZE].copy_from_slice(&LARGE_FONTSET);
+
         new_emu
     }
 
@@ -73,7 +182,8 @@ impl Emu {
     pub fn reset(&mut self) {
         self.pc = START_ADDR;
         self.ram = [0; RAM_SIZE];
-        self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+        self.screen = [false; HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT];
+        self.hires = false;
         self.v_reg = [0; NUM_REGS];
         self.i_reg = 0;
         self.sp = 0;
@@ -81,9 +191,56 @@ impl Emu {
         self.keys = [false; NUM_KEYS];
         self.dt = 0;
         self.st = 0;
+        self.flags = [0; NUM_FLAGS];
         self.ram[..FONTSET_SIZE].copy_from_slice(&FONTSET);
+        self.ram[FONTSET_SIZE..FONTSET_SIZE + LARGE_FONTSET_SIZE].copy_from_slice(&LARGE_FONTSET);
+    }
+
+    // Lets a frontend report a key's up/down state so FX0A and EX9E/EXA1 can read it
+    pub fn keypress(&mut self, idx: usize, pressed: bool) {
+        self.keys[idx] = pressed;
+    }
+
+    // True while running in Super-CHIP's 128x64 hi-res mode
+    pub fn is_hires(&self) -> bool {
+        self.hires
+    }
+
+    // Current display dimensions, so a frontend can size its canvas correctly in
+    // both lo-res (64x32) and hi-res (128x64) mode
+    pub fn screen_size(&self) -> (usize, usize) {
+        self.dimensions()
+    }
+
+    // Returns the (width, height) of the active display mode
+    fn dimensions(&self) -> (usize, usize) {
+        if self.hires {
+            (HIRES_SCREEN_WIDTH, HIRES_SCREEN_HEIGHT)
+        } else {
+            (SCREEN_WIDTH, SCREEN_HEIGHT)
+        }
+    }
+
+    // XORs a single sprite pixel onto the screen at the given raw (unwrapped)
+    // coordinates, honoring the clip_sprites quirk: when clipping, pixels past
+    // the edge are dropped instead of wrapping around. Returns whether the
+    // pixel was a collision (was already set).
+    fn plot_sprite_pixel(&mut self, raw_x: u16, raw_y: u16, width: usize, height: usize) -> bool {
+        let (x, y) = if self.quirks.clip_sprites {
+            if raw_x as usize >= width || raw_y as usize >= height {
+                return false;
+            }
+            (raw_x as usize, raw_y as usize)
+        } else {
+            (raw_x as usize % width, raw_y as usize % height)
+        };
+
+        let idx = y * width + x;
+        let collided = self.screen[idx];
+        self.screen[idx] ^= true;
+        collided
     }
-    
+
     // Used to push value to stack and increment Stack Pointer
     fn push(&mut self, val: u16) {
         self.stack[self.sp as usize] = val;
@@ -117,7 +274,60 @@ impl Emu {
             
             // CLS: Clear Screen
             (0, 0, 0xE, 0) => {
-                self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
+                self.screen = [false; HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT];
+            },
+
+            // 00CN - Scroll display down N pixels (SCHIP)
+            (0, 0, 0xC, _) => {
+                let n = digit4 as usize;
+                let (width, height) = self.dimensions();
+                for y in (0..height).rev() {
+                    for x in 0..width {
+                        self.screen[y * width + x] = if y >= n {
+                            self.screen[(y - n) * width + x]
+                        } else {
+                            false
+                        };
+                    }
+                }
+            },
+
+            // 00FB - Scroll display right 4 pixels (SCHIP)
+            (0, 0, 0xF, 0xB) => {
+                let (width, height) = self.dimensions();
+                for y in 0..height {
+                    for x in (0..width).rev() {
+                        self.screen[y * width + x] = if x >= 4 {
+                            self.screen[y * width + x - 4]
+                        } else {
+                            false
+                        };
+                    }
+                }
+            },
+
+            // 00FC - Scroll display left 4 pixels (SCHIP)
+            (0, 0, 0xF, 0xC) => {
+                let (width, height) = self.dimensions();
+                for y in 0..height {
+                    for x in 0..width {
+                        self.screen[y * width + x] = if x + 4 < width {
+                            self.screen[y * width + x + 4]
+                        } else {
+                            false
+                        };
+                    }
+                }
+            },
+
+            // 00FE - Disable hi-res mode (SCHIP)
+            (0, 0, 0xF, 0xE) => {
+                self.hires = false;
+            },
+
+            // 00FF - Enable hi-res mode (SCHIP)
+            (0, 0, 0xF, 0xF) => {
+                self.hires = true;
             },
 
             // RET: Return from subroutine
@@ -225,6 +435,307 @@ impl Emu {
                 self.v_reg[x] ^= self.v_reg[y];
             }
 
+            // 8XY4 - VX += VY, with carry
+            // VF is set to 1 if the addition overflows, 0 otherwise
+            (8, _, _, 4) => {
+                let x = digit2 as usize;
+                let y = digit3 as usize;
+                let (new_vx, carry) = self.v_reg[x].overflowing_add(self.v_reg[y]);
+                let new_vf = if carry { 1 } else { 0 };
+
+                self.v_reg[x] = new_vx;
+                self.v_reg[0xF] = new_vf;
+            }
+
+            // 8XY5 - VX -= VY, with borrow
+            // VF is set to 0 if the subtraction borrows, 1 otherwise
+            (8, _, _, 5) => {
+                let x = digit2 as usize;
+                let y = digit3 as usize;
+                let (new_vx, borrow) = self.v_reg[x].overflowing_sub(self.v_reg[y]);
+                let new_vf = if borrow { 0 } else { 1 };
+
+                self.v_reg[x] = new_vx;
+                self.v_reg[0xF] = new_vf;
+            }
+
+            // 8XY6 - Right Shift
+            // VF is set to the bit that was shifted out, then VX is shifted right by 1.
+            // Per the shift_in_place quirk, VY is copied into VX before shifting unless
+            // the interpreter shifts VX in place (see Quirks::shift_in_place).
+            (8, _, _, 6) => {
+                let x = digit2 as usize;
+                let y = digit3 as usize;
+                if !self.quirks.shift_in_place {
+                    self.v_reg[x] = self.v_reg[y];
+                }
+                let lsb = self.v_reg[x] & 1;
+                self.v_reg[x] >>= 1;
+                self.v_reg[0xF] = lsb;
+            }
+
+            // 8XY7 - VX = VY - VX, with borrow
+            // VF is set to 0 if the subtraction borrows, 1 otherwise
+            (8, _, _, 7) => {
+                let x = digit2 as usize;
+                let y = digit3 as usize;
+                let (new_vx, borrow) = self.v_reg[y].overflowing_sub(self.v_reg[x]);
+                let new_vf = if borrow { 0 } else { 1 };
+
+                self.v_reg[x] = new_vx;
+                self.v_reg[0xF] = new_vf;
+            }
+
+            // 8XYE - Left Shift
+            // VF is set to the bit that was shifted out, then VX is shifted left by 1.
+            // Subject to the same shift_in_place quirk as 8XY6.
+            (8, _, _, 0xE) => {
+                let x = digit2 as usize;
+                let y = digit3 as usize;
+                if !self.quirks.shift_in_place {
+                    self.v_reg[x] = self.v_reg[y];
+                }
+                let msb = (self.v_reg[x] >> 7) & 1;
+                self.v_reg[x] <<= 1;
+                self.v_reg[0xF] = msb;
+            }
+
+            // 9XY0 - Skip next if VX != VY
+            // SKIP VX != VY
+            (9, _, _, 0) => {
+                let x = digit2 as usize;
+                let y = digit3 as usize;
+                if self.v_reg[x] != self.v_reg[y] {
+                    self.pc += 2;
+                }
+            },
+
+            // ANNN - i_reg = NNN
+            // Sets the I Register to the given address
+            (0xA, _, _, _) => {
+                let nnn = op & 0xFFF;
+                self.i_reg = nnn;
+            },
+
+            // BNNN - Jump to V0 + NNN (or VX + NNN under the jump_uses_vx quirk)
+            // JMP V0, NNN
+            (0xB, _, _, _) => {
+                let nnn = op & 0xFFF;
+                let base = if self.quirks.jump_uses_vx {
+                    self.v_reg[digit2 as usize]
+                } else {
+                    self.v_reg[0]
+                };
+                self.pc = (base as u16) + nnn;
+            },
+
+            // CXNN - VX = random_byte & NN
+            (0xC, _, _, _) => {
+                let x = digit2 as usize;
+                let nn = (op & 0xFF) as u8;
+                let rng: u8 = random();
+                self.v_reg[x] = rng & nn;
+            },
+
+            // DXY0 - Draw 16x16 Sprite (SCHIP, hi-res mode)
+            // DRW VX, VY, 0
+            // Same as DXYN but always draws a fixed 16x16 sprite, stored as 32 bytes
+            // (two bytes per row) starting at i_reg.
+            (0xD, _, _, 0) => {
+                let x_coord = self.v_reg[digit2 as usize] as u16;
+                let y_coord = self.v_reg[digit3 as usize] as u16;
+                let (width, height) = self.dimensions();
+
+                let mut flipped = false;
+                for row in 0..16 {
+                    let addr = self.i_reg + row * 2;
+                    let hi = self.ram[addr as usize] as u16;
+                    let lo = self.ram[(addr + 1) as usize] as u16;
+                    let pixels = (hi << 8) | lo;
+
+                    for column in 0..16 {
+                        if (pixels & (0x8000 >> column)) != 0 {
+                            flipped |= self.plot_sprite_pixel(x_coord + column, y_coord + row, width, height);
+                        }
+                    }
+                }
+
+                self.v_reg[0xF] = if flipped { 1 } else { 0 };
+            },
+
+            // DXYN - Draw Sprite
+            // DRAW VX, VY, N
+            // Draws an N-tall sprite stored at the address in i_reg to the screen, at
+            // position (VX, VY). Each row is one byte, MSB first, and drawing XORs the
+            // sprite's pixels onto the screen so sprites can also erase themselves.
+            // VF is set to 1 if any pixel was flipped from set to unset (collision).
+            (0xD, _, _, _) => {
+                let x_coord = self.v_reg[digit2 as usize] as u16;
+                let y_coord = self.v_reg[digit3 as usize] as u16;
+                let num_rows = digit4;
+                let (width, height) = self.dimensions();
+
+                let mut flipped = false;
+                for row in 0..num_rows {
+                    let addr = self.i_reg + row;
+                    let pixels = self.ram[addr as usize];
+
+                    for column in 0..8 {
+                        // Only draw if the current bit is a 1
+                        if (pixels & (0b1000_0000 >> column)) != 0 {
+                            flipped |= self.plot_sprite_pixel(x_coord + column, y_coord + row, width, height);
+                        }
+                    }
+                }
+
+                self.v_reg[0xF] = if flipped { 1 } else { 0 };
+            },
+
+            // EX9E - Skip next if key in VX is pressed
+            // SKIP KEY PRESS VX
+            // VX holds a full byte, not just a nibble, so it's masked down to the
+            // 0-15 key range before indexing `keys`.
+            (0xE, _, 9, 0xE) => {
+                let x = digit2 as usize;
+                let vx = self.v_reg[x] & 0xF;
+                if self.keys[vx as usize] {
+                    self.pc += 2;
+                }
+            },
+
+            // EXA1 - Skip next if key in VX is not pressed
+            // SKIP KEY RELEASE VX
+            // Subject to the same VX mask as EX9E.
+            (0xE, _, 0xA, 1) => {
+                let x = digit2 as usize;
+                let vx = self.v_reg[x] & 0xF;
+                if !self.keys[vx as usize] {
+                    self.pc += 2;
+                }
+            },
+
+            // FX0A - Wait for key press, blocking
+            // WAIT KEY, VX
+            // Repeatedly runs this same opcode until a key is pressed, then stores its
+            // index into VX. Blocking is implemented by decrementing the PC by 2 so the
+            // same instruction re-executes on the next tick when no key is down.
+            (0xF, _, 0, 0xA) => {
+                let x = digit2 as usize;
+                let mut pressed = false;
+                for i in 0..NUM_KEYS {
+                    if self.keys[i] {
+                        self.v_reg[x] = i as u8;
+                        pressed = true;
+                        break;
+                    }
+                }
+
+                if !pressed {
+                    self.pc -= 2;
+                }
+            },
+
+            // FX07 - VX = dt
+            (0xF, _, 0, 7) => {
+                let x = digit2 as usize;
+                self.v_reg[x] = self.dt;
+            },
+
+            // FX15 - dt = VX
+            (0xF, _, 1, 5) => {
+                let x = digit2 as usize;
+                self.dt = self.v_reg[x];
+            },
+
+            // FX18 - st = VX
+            (0xF, _, 1, 8) => {
+                let x = digit2 as usize;
+                self.st = self.v_reg[x];
+            },
+
+            // FX1E - i_reg += VX
+            (0xF, _, 1, 0xE) => {
+                let x = digit2 as usize;
+                let vx = self.v_reg[x] as u16;
+                self.i_reg = self.i_reg.wrapping_add(vx);
+            },
+
+            // FX29 - Set i_reg to address of font character in VX
+            // Each font glyph is 5 bytes, stored back to back at the start of RAM
+            (0xF, _, 2, 9) => {
+                let x = digit2 as usize;
+                let c = self.v_reg[x] as u16;
+                self.i_reg = c * 5;
+            },
+
+            // FX30 - Set i_reg to address of large font character in VX (SCHIP)
+            // Each large glyph is 10 bytes, stored right after the regular FONTSET
+            (0xF, _, 3, 0) => {
+                let x = digit2 as usize;
+                let c = self.v_reg[x] as u16;
+                self.i_reg = FONTSET_SIZE as u16 + c * 10;
+            },
+
+            // FX33 - Store BCD encoding of VX into ram[i_reg..i_reg+3]
+            (0xF, _, 3, 3) => {
+                let x = digit2 as usize;
+                let vx = self.v_reg[x] as f32;
+
+                // Fetch the hundreds digit by dividing by 100 and tossing the decimal
+                let hundreds = (vx / 100.0).floor() as u8;
+                // Fetch the tens digit by dividing by 10, tossing the ones digit and the
+                // decimal
+                let tens = ((vx / 10.0) % 10.0).floor() as u8;
+                // Fetch the ones digit by tossing the hundreds and the tens
+                let ones = (vx % 10.0) as u8;
+
+                self.ram[self.i_reg as usize] = hundreds;
+                self.ram[(self.i_reg + 1) as usize] = tens;
+                self.ram[(self.i_reg + 2) as usize] = ones;
+            },
+
+            // FX55 - Store V0..=VX into ram, starting at i_reg. Under the
+            // load_store_leaves_i quirk, i_reg is left unchanged; otherwise it is
+            // incremented by X + 1, as on the original COSMAC VIP.
+            (0xF, _, 5, 5) => {
+                let x = digit2 as usize;
+                let i = self.i_reg as usize;
+                for idx in 0..=x {
+                    self.ram[i + idx] = self.v_reg[idx];
+                }
+                if !self.quirks.load_store_leaves_i {
+                    self.i_reg += x as u16 + 1;
+                }
+            },
+
+            // FX65 - Load V0..=VX from ram, starting at i_reg. Subject to the same
+            // load_store_leaves_i quirk as FX55.
+            (0xF, _, 6, 5) => {
+                let x = digit2 as usize;
+                let i = self.i_reg as usize;
+                for idx in 0..=x {
+                    self.v_reg[idx] = self.ram[i + idx];
+                }
+                if !self.quirks.load_store_leaves_i {
+                    self.i_reg += x as u16 + 1;
+                }
+            },
+
+            // FX75 - Save V0..=VX into the persistent RPL flags registers (SCHIP).
+            // The RPL flags only ever cover R0-R7, so X is clamped to the top of
+            // that range instead of trusting the raw nibble (which can be up to 15).
+            (0xF, _, 7, 5) => {
+                let x = (digit2 as usize).min(NUM_FLAGS - 1);
+                self.flags[..=x].copy_from_slice(&self.v_reg[..=x]);
+            },
+
+            // FX85 - Restore V0..=VX from the persistent RPL flags registers (SCHIP).
+            // Subject to the same X clamp as FX75.
+            (0xF, _, 8, 5) => {
+                let x = (digit2 as usize).min(NUM_FLAGS - 1);
+                self.v_reg[..=x].copy_from_slice(&self.flags[..=x]);
+            },
+
             // Panics when an unimplemented opcode is run
             (_, _, _, _) => unimplemented!("Unimplemented opcode: {}", op),
         }
@@ -252,12 +763,414 @@ impl Emu {
 
         if self.st > 0 {
             if self.st == 1 {
-                // BEEP 
+                // BEEP
                 // NOTE: (audio will not be implemented in this emulator but this is the format)
             }
             self.st -= 1;
         }
     }
+
+    // Serializes the full machine state into a compact, versioned byte blob, so a
+    // frontend can implement save/load or rewind. Fields are packed in a fixed
+    // order after a one-byte format version. `quirks` is not included; see
+    // SNAPSHOT_LEN for why.
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(SNAPSHOT_LEN);
+
+        out.push(SNAPSHOT_VERSION);
+        out.extend_from_slice(&self.pc.to_be_bytes());
+        out.extend_from_slice(&self.ram);
+        out.extend(self.screen.iter().map(|&pixel| pixel as u8));
+        out.push(self.hires as u8);
+        out.extend_from_slice(&self.v_reg);
+        out.extend_from_slice(&self.i_reg.to_be_bytes());
+        out.extend_from_slice(&self.sp.to_be_bytes());
+        for addr in self.stack.iter() {
+            out.extend_from_slice(&addr.to_be_bytes());
+        }
+        out.extend(self.keys.iter().map(|&key| key as u8));
+        out.push(self.dt);
+        out.push(self.st);
+        out.extend_from_slice(&self.flags);
+
+        out
+    }
+
+    // Restores machine state from a blob produced by `snapshot`. Rejects data of
+    // the wrong length or an unsupported format version instead of panicking.
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), String> {
+        if bytes.len() != SNAPSHOT_LEN {
+            return Err(format!(
+                "snapshot has wrong length: expected {} bytes, got {}",
+                SNAPSHOT_LEN,
+                bytes.len()
+            ));
+        }
+
+        let version = bytes[0];
+        if version != SNAPSHOT_VERSION {
+            return Err(format!("unsupported snapshot version: {}", version));
+        }
+
+        let mut pos = 1;
+
+        let pc = u16::from_be_bytes([bytes[pos], bytes[pos + 1]]);
+        pos += 2;
+
+        let ram_end = pos + RAM_SIZE;
+        let mut ram = [0u8; RAM_SIZE];
+        ram.copy_from_slice(&bytes[pos..ram_end]);
+        pos = ram_end;
+
+        let screen_end = pos + HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT;
+        let mut screen = [false; HIRES_SCREEN_WIDTH * HIRES_SCREEN_HEIGHT];
+        for (dst, &src) in screen.iter_mut().zip(&bytes[pos..screen_end]) {
+            *dst = src != 0;
+        }
+        pos = screen_end;
+
+        let hires = bytes[pos] != 0;
+        pos += 1;
+
+        let v_reg_end = pos + NUM_REGS;
+        let mut v_reg = [0u8; NUM_REGS];
+        v_reg.copy_from_slice(&bytes[pos..v_reg_end]);
+        pos = v_reg_end;
+
+        let i_reg = u16::from_be_bytes([bytes[pos], bytes[pos + 1]]);
+        pos += 2;
+
+        let sp = u16::from_be_bytes([bytes[pos], bytes[pos + 1]]);
+        pos += 2;
+
+        let mut stack = [0u16; STACK_SIZE];
+        for slot in stack.iter_mut() {
+            *slot = u16::from_be_bytes([bytes[pos], bytes[pos + 1]]);
+            pos += 2;
+        }
+
+        let keys_end = pos + NUM_KEYS;
+        let mut keys = [false; NUM_KEYS];
+        for (dst, &src) in keys.iter_mut().zip(&bytes[pos..keys_end]) {
+            *dst = src != 0;
+        }
+        pos = keys_end;
+
+        let dt = bytes[pos];
+        pos += 1;
+        let st = bytes[pos];
+        pos += 1;
+
+        let flags_end = pos + NUM_FLAGS;
+        let mut flags = [0u8; NUM_FLAGS];
+        flags.copy_from_slice(&bytes[pos..flags_end]);
+
+        self.pc = pc;
+        self.ram = ram;
+        self.screen = screen;
+        self.hires = hires;
+        self.v_reg = v_reg;
+        self.i_reg = i_reg;
+        self.sp = sp;
+        self.stack = stack;
+        self.keys = keys;
+        self.dt = dt;
+        self.st = st;
+        self.flags = flags;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_preserves_hires_and_flags() {
+        let mut emu = Emu::new();
+        emu.execute(0x00FF); // enable hi-res mode
+        emu.flags = [0xAB; NUM_FLAGS];
+        emu.v_reg[3] = 0x42;
+        emu.i_reg = 0x300;
+
+        let blob = emu.snapshot();
+
+        let mut restored = Emu::new();
+        restored.restore(&blob).unwrap();
+
+        assert!(restored.is_hires());
+        assert_eq!(restored.flags, [0xAB; NUM_FLAGS]);
+        assert_eq!(restored.v_reg[3], 0x42);
+        assert_eq!(restored.i_reg, 0x300);
+    }
+
+    #[test]
+    fn restore_rejects_wrong_length() {
+        let mut emu = Emu::new();
+        assert!(emu.restore(&[0u8; 4]).is_err());
+    }
+
+    #[test]
+    fn restore_rejects_unsupported_version() {
+        let mut emu = Emu::new();
+        let mut blob = emu.snapshot();
+        blob[0] = SNAPSHOT_VERSION + 1;
+        assert!(emu.restore(&blob).is_err());
+    }
+}
+
+#[cfg(test)]
+mod arithmetic_tests {
+    use super::*;
+
+    #[test]
+    fn axy4_add_sets_vf_on_carry() {
+        let mut emu = Emu::new();
+        emu.v_reg[0] = 0xFF;
+        emu.v_reg[1] = 1;
+        emu.execute(0x8014); // 8XY4: V0 += V1
+
+        assert_eq!(emu.v_reg[0], 0);
+        assert_eq!(emu.v_reg[0xF], 1);
+    }
+
+    #[test]
+    fn axy4_add_clears_vf_without_carry() {
+        let mut emu = Emu::new();
+        emu.v_reg[0] = 1;
+        emu.v_reg[1] = 2;
+        emu.execute(0x8014);
+
+        assert_eq!(emu.v_reg[0], 3);
+        assert_eq!(emu.v_reg[0xF], 0);
+    }
+
+    #[test]
+    fn axy5_sub_clears_vf_on_borrow() {
+        let mut emu = Emu::new();
+        emu.v_reg[0] = 1;
+        emu.v_reg[1] = 2;
+        emu.execute(0x8015); // 8XY5: V0 -= V1
+
+        assert_eq!(emu.v_reg[0], 0xFF);
+        assert_eq!(emu.v_reg[0xF], 0);
+    }
+
+    #[test]
+    fn axy5_sub_sets_vf_without_borrow() {
+        let mut emu = Emu::new();
+        emu.v_reg[0] = 5;
+        emu.v_reg[1] = 2;
+        emu.execute(0x8015);
+
+        assert_eq!(emu.v_reg[0], 3);
+        assert_eq!(emu.v_reg[0xF], 1);
+    }
+
+    #[test]
+    fn axy7_subn_clears_vf_on_borrow() {
+        let mut emu = Emu::new();
+        emu.v_reg[0] = 5; // VX
+        emu.v_reg[1] = 2; // VY
+        emu.execute(0x8017); // 8XY7: V0 = V1 - V0
+
+        assert_eq!(emu.v_reg[0], 0xFD); // 2 - 5 wraps
+        assert_eq!(emu.v_reg[0xF], 0);
+    }
+
+    #[test]
+    fn axy7_subn_sets_vf_without_borrow() {
+        let mut emu = Emu::new();
+        emu.v_reg[0] = 2; // VX
+        emu.v_reg[1] = 5; // VY
+        emu.execute(0x8017); // 8XY7: V0 = V1 - V0
+
+        assert_eq!(emu.v_reg[0], 3);
+        assert_eq!(emu.v_reg[0xF], 1);
+    }
+}
+
+#[cfg(test)]
+mod quirk_tests {
+    use super::*;
+
+    #[test]
+    fn shift_in_place_quirk_toggles_8xy6_source() {
+        let mut copy_first = Emu::with_quirks(Quirks {
+            shift_in_place: false,
+            ..Quirks::default()
+        });
+        copy_first.v_reg[1] = 0b0000_0010; // VX
+        copy_first.v_reg[2] = 0b0000_0101; // VY
+        copy_first.execute(0x8126); // 8XY6: VX = V1, VY = V2
+        assert_eq!(copy_first.v_reg[1], 0b0000_0010); // VY (5) shifted right
+        assert_eq!(copy_first.v_reg[0xF], 1); // VY's LSB
+
+        let mut in_place = Emu::with_quirks(Quirks {
+            shift_in_place: true,
+            ..Quirks::default()
+        });
+        in_place.v_reg[1] = 0b0000_0010;
+        in_place.v_reg[2] = 0b0000_0101;
+        in_place.execute(0x8126);
+        assert_eq!(in_place.v_reg[1], 0b0000_0001); // VX (2) shifted right
+        assert_eq!(in_place.v_reg[0xF], 0); // VX's LSB
+    }
+
+    #[test]
+    fn jump_uses_vx_quirk_toggles_bnnn_base() {
+        let mut v0_based = Emu::with_quirks(Quirks {
+            jump_uses_vx: false,
+            ..Quirks::default()
+        });
+        v0_based.v_reg[0] = 0x10;
+        v0_based.v_reg[2] = 0x20;
+        v0_based.execute(0xB200); // BNNN with NNN = 0x200, digit2 = 2
+        assert_eq!(v0_based.pc, 0x210);
+
+        let mut vx_based = Emu::with_quirks(Quirks {
+            jump_uses_vx: true,
+            ..Quirks::default()
+        });
+        vx_based.v_reg[0] = 0x10;
+        vx_based.v_reg[2] = 0x20;
+        vx_based.execute(0xB200);
+        assert_eq!(vx_based.pc, 0x220);
+    }
+
+    #[test]
+    fn load_store_leaves_i_quirk_toggles_fx55_fx65_advance() {
+        let mut advances = Emu::with_quirks(Quirks {
+            load_store_leaves_i: false,
+            ..Quirks::default()
+        });
+        advances.i_reg = 0x300;
+        advances.execute(0xF255); // FX55 with X = 2
+        assert_eq!(advances.i_reg, 0x303);
+
+        let mut leaves = Emu::with_quirks(Quirks {
+            load_store_leaves_i: true,
+            ..Quirks::default()
+        });
+        leaves.i_reg = 0x300;
+        leaves.execute(0xF255);
+        assert_eq!(leaves.i_reg, 0x300);
+    }
+
+    #[test]
+    fn clip_sprites_quirk_toggles_dxyn_edge_behavior() {
+        let mut clipping = Emu::with_quirks(Quirks {
+            clip_sprites: true,
+            ..Quirks::default()
+        });
+        clipping.i_reg = 0x300;
+        clipping.ram[0x300] = 0b1111_1111;
+        clipping.v_reg[0] = (SCREEN_WIDTH - 4) as u8; // sprite hangs off the right edge
+        clipping.v_reg[1] = 0;
+        clipping.execute(0xD011); // DXYN, N = 1
+        assert!(!clipping.screen[0]); // nothing wrapped onto the left edge
+
+        let mut wrapping = Emu::with_quirks(Quirks {
+            clip_sprites: false,
+            ..Quirks::default()
+        });
+        wrapping.i_reg = 0x300;
+        wrapping.ram[0x300] = 0b1111_1111;
+        wrapping.v_reg[0] = (SCREEN_WIDTH - 4) as u8;
+        wrapping.v_reg[1] = 0;
+        wrapping.execute(0xD011);
+        assert!(wrapping.screen[0]); // the last 4 bits wrapped onto the left edge
+    }
+}
+
+#[cfg(test)]
+mod drawing_tests {
+    use super::*;
+
+    #[test]
+    fn dxyn_draws_sprite_without_collision() {
+        let mut emu = Emu::new();
+        emu.i_reg = 0x300;
+        emu.ram[0x300] = 0b1111_0000;
+        emu.v_reg[0] = 0;
+        emu.v_reg[1] = 0;
+
+        emu.execute(0xD011); // DRW V0, V1, 1
+
+        assert!(emu.screen[0]);
+        assert!(emu.screen[1]);
+        assert!(emu.screen[2]);
+        assert!(emu.screen[3]);
+        assert!(!emu.screen[4]);
+        assert_eq!(emu.v_reg[0xF], 0); // nothing was already set, so no collision
+    }
+
+    #[test]
+    fn dxyn_sets_vf_on_collision_and_erases_overlap() {
+        let mut emu = Emu::new();
+        emu.i_reg = 0x300;
+        emu.ram[0x300] = 0b1111_0000;
+        emu.v_reg[0] = 0;
+        emu.v_reg[1] = 0;
+
+        emu.execute(0xD011); // first draw: sets pixels 0-3
+        emu.execute(0xD011); // drawing the same sprite again XORs it back off
+
+        assert!(!emu.screen[0]);
+        assert!(!emu.screen[3]);
+        assert_eq!(emu.v_reg[0xF], 1); // the second draw collided with the first
+    }
+}
+
+#[cfg(test)]
+mod schip_flags_tests {
+    use super::*;
+
+    #[test]
+    fn fx75_fx85_round_trip_through_flags() {
+        let mut emu = Emu::new();
+        emu.v_reg[..4].copy_from_slice(&[0x11, 0x22, 0x33, 0x44]);
+        emu.execute(0xF375); // FX75, X = 3: save V0..=V3 into flags
+
+        emu.v_reg[..4].copy_from_slice(&[0, 0, 0, 0]);
+        emu.execute(0xF385); // FX85, X = 3: restore V0..=V3 from flags
+
+        assert_eq!(&emu.v_reg[..4], &[0x11, 0x22, 0x33, 0x44]);
+    }
+
+    #[test]
+    fn fx75_fx85_clamp_x_above_flags_range() {
+        let mut emu = Emu::new();
+        emu.v_reg = [0xAA; NUM_REGS]; // X = 0xF, well past the 8 RPL flags slots
+
+        // Neither opcode should panic despite the full nibble being out of range
+        emu.execute(0xFF75);
+        emu.execute(0xFF85);
+
+        assert_eq!(emu.flags, [0xAA; NUM_FLAGS]);
+        assert_eq!(&emu.v_reg[..NUM_FLAGS], &[0xAA; NUM_FLAGS]);
+    }
+}
+
+#[cfg(test)]
+mod keypad_tests {
+    use super::*;
+
+    #[test]
+    fn ex9e_exa1_mask_vx_above_key_range() {
+        let mut emu = Emu::new();
+        emu.keypress(3, true);
+        emu.v_reg[0] = 0xF3; // byte value whose low nibble (3) is a pressed key
+
+        let pc_before = emu.pc;
+        emu.execute(0xE09E); // EX9E, X = 0: should skip, key 3 is pressed
+        assert_eq!(emu.pc, pc_before + 2);
+
+        let pc_before = emu.pc;
+        emu.execute(0xE0A1); // EXA1, X = 0: should not skip, key 3 is pressed
+        assert_eq!(emu.pc, pc_before);
+    }
 }
 
 